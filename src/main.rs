@@ -1,10 +1,13 @@
+use std::collections::HashMap;
 use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
 use std::time::Duration;
 
 use chrono::prelude::*;
 
 use clap::Parser;
 use rusqlite::{Connection, Result as SQLResult};
+use serde::Deserialize;
 use sntpc::{Error, NtpContext, NtpResult, NtpTimestampGenerator, NtpUdpSocket};
 
 use cursive::views::{Dialog, DummyView, LinearLayout, RadioGroup};
@@ -13,10 +16,38 @@ const DEFAULT_NAME: &str = "main";
 const DEFAULT_DATABASE: &str = "watch.sqlite";
 const DEFAULT_COMMENT: &str = "";
 
+/// NTP hosts polled when neither `--server` nor a config profile provides one.
+const DEFAULT_SERVERS: &[&str] = &["time.cloudflare.com:123", "pool.ntp.org:123"];
+
+/// Number of attempts made against each server; we keep the one with the
+/// lowest round-trip delay since that one is least jitter-corrupted.
+const DEFAULT_RETRIES: usize = 4;
+
+/// Round-trip delays above this are too noisy to trust (microseconds).
+const DEFAULT_MAX_ROUNDTRIP_US: u64 = 150_000;
+
+/// Config file read relative to `$HOME` when `--config` isn't given.
+const DEFAULT_CONFIG_PATH: &str = ".config/accurate.toml";
+
+/// Default `--predict` horizon: a week out.
+const DEFAULT_PREDICT_HORIZON_SECS: i64 = 7 * 24 * 3600;
+
+/// Default estimated delay between reading the NTP reference and finishing
+/// the crown adjustment.
+const DEFAULT_MANIPULATION_DELAY_SECS: i64 = 5;
+
+/// A measurement only counts as a check-in on a pending `--predict` for the
+/// same watch if it falls within this many seconds of the target.
+const PREDICTION_TOLERANCE_SECS: i64 = 3600;
+
+/// Number of past prediction-error readings averaged into the bias applied
+/// to the next `--predict` projection.
+const PREDICTION_BIAS_WINDOW: usize = 5;
+
 /// Measure your watch accuracy on the long run
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+struct Cli {
     /// Synchronize your watch
     #[arg(short, long, default_value_t = false)]
     sync: bool,
@@ -25,13 +56,236 @@ struct Args {
     #[arg(short, long, default_value_t = DEFAULT_NAME.to_string())]
     name: String,
 
-    /// Database file
-    #[arg(short, long, default_value_t = DEFAULT_DATABASE.to_string())]
-    data: String,
+    /// Database file. Falls back to the config profile's database, then
+    /// `watch.sqlite`, when not given.
+    #[arg(short, long)]
+    data: Option<String>,
+
+    /// Comment of the measurement if any. Falls back to the config
+    /// profile's comment, then none, when not given.
+    #[arg(short, long)]
+    comment: Option<String>,
+
+    /// NTP server to query, e.g. "pool.ntp.org:123"; may be given multiple
+    /// times. Falls back to the config profile's servers, then a short
+    /// list of public servers, when omitted.
+    #[arg(long = "server", value_name = "HOST")]
+    servers: Vec<String>,
+
+    /// Print a drift-rate report for this watch instead of taking a new
+    /// measurement
+    #[arg(long, default_value_t = false)]
+    report: bool,
 
-    /// Comment of the measurement if any
-    #[arg(short, long, default_value_t = DEFAULT_COMMENT.to_string())]
+    /// Compute the crown-set time that will make the watch read correctly
+    /// at a future moment, using the drift rate fitted since the last sync
+    #[arg(long, default_value_t = false)]
+    predict: bool,
+
+    /// How far in the future the watch should read correctly, in seconds
+    /// (used with --predict)
+    #[arg(long, default_value_t = DEFAULT_PREDICT_HORIZON_SECS)]
+    predict_horizon_secs: i64,
+
+    /// Estimated delay between reading the reference time and finishing
+    /// the crown adjustment, in seconds (used with --predict)
+    #[arg(long, default_value_t = DEFAULT_MANIPULATION_DELAY_SECS)]
+    manipulation_delay_secs: i64,
+
+    /// Number of NTP attempts per server. Falls back to the config's
+    /// global retries, then a built-in default, when not given.
+    #[arg(long)]
+    retries: Option<usize>,
+
+    /// Maximum acceptable NTP round-trip delay, in microseconds. Falls
+    /// back to the config's global max_roundtrip_us, then a built-in
+    /// default, when not given.
+    #[arg(long)]
+    max_roundtrip_us: Option<u64>,
+
+    /// TOML config file defining watch profiles. Defaults to
+    /// `~/.config/accurate.toml` when present.
+    #[arg(long)]
+    config: Option<String>,
+}
+
+/// Fully resolved settings for this run: explicit CLI flags merged over the
+/// `[watch.<name>]` config profile, merged over the built-in defaults.
+struct Args {
+    sync: bool,
+    name: String,
+    data: String,
     comment: String,
+    servers: Vec<String>,
+    report: bool,
+    predict: bool,
+    predict_horizon_secs: i64,
+    manipulation_delay_secs: i64,
+    retries: usize,
+    max_roundtrip_us: u64,
+}
+
+/// Per-watch settings loadable from the config file, letting a collector
+/// track several watches without memorizing long command lines.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct WatchProfile {
+    database: Option<String>,
+    comment: Option<String>,
+    servers: Vec<String>,
+}
+
+/// Top-level shape of the TOML config file: global defaults plus a
+/// `[watch.<name>]` table per tracked watch.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    retries: Option<usize>,
+    max_roundtrip_us: Option<u64>,
+    watch: HashMap<String, WatchProfile>,
+}
+
+/// `~/.config/accurate.toml`, if `$HOME` is set.
+fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(DEFAULT_CONFIG_PATH))
+}
+
+/// Load the config file at `path`, or the default location if `path` is
+/// `None`. Missing files are silent (most setups won't have one); a present
+/// but unparsable file is reported and treated as empty.
+fn load_config(path: Option<&str>) -> Config {
+    let path = path.map(PathBuf::from).or_else(default_config_path);
+    let Some(path) = path else {
+        return Config::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents).unwrap_or_else(|err| {
+            println!("Failed to parse config {:?}: {err}", path);
+            Config::default()
+        }),
+        Err(_) => Config::default(),
+    }
+}
+
+/// Merge explicit CLI flags over the `[watch.<name>]` config profile, over
+/// the built-in defaults. `cli`'s fields are only `Some`/non-empty when the
+/// user actually passed them, so this can't mistake an explicit flag that
+/// happens to match a built-in default for "not given".
+fn resolve_args(cli: Cli, config: &Config) -> Args {
+    let profile = config.watch.get(&cli.name);
+
+    let data = cli
+        .data
+        .or_else(|| profile.and_then(|p| p.database.clone()))
+        .unwrap_or_else(|| DEFAULT_DATABASE.to_string());
+    let comment = cli
+        .comment
+        .or_else(|| profile.and_then(|p| p.comment.clone()))
+        .unwrap_or_else(|| DEFAULT_COMMENT.to_string());
+    let servers = if !cli.servers.is_empty() {
+        cli.servers
+    } else {
+        profile.map(|p| p.servers.clone()).unwrap_or_default()
+    };
+    let retries = cli.retries.or(config.retries).unwrap_or(DEFAULT_RETRIES);
+    let max_roundtrip_us = cli
+        .max_roundtrip_us
+        .or(config.max_roundtrip_us)
+        .unwrap_or(DEFAULT_MAX_ROUNDTRIP_US);
+
+    Args {
+        sync: cli.sync,
+        name: cli.name,
+        data,
+        comment,
+        servers,
+        report: cli.report,
+        predict: cli.predict,
+        predict_horizon_secs: cli.predict_horizon_secs,
+        manipulation_delay_secs: cli.manipulation_delay_secs,
+        retries,
+        max_roundtrip_us,
+    }
+}
+
+#[cfg(test)]
+mod resolve_args_tests {
+    use super::*;
+
+    /// A `Cli` as if no flags were passed, for the given watch name.
+    fn bare_cli(name: &str) -> Cli {
+        Cli {
+            sync: false,
+            name: name.to_string(),
+            data: None,
+            comment: None,
+            servers: Vec::new(),
+            report: false,
+            predict: false,
+            predict_horizon_secs: DEFAULT_PREDICT_HORIZON_SECS,
+            manipulation_delay_secs: DEFAULT_MANIPULATION_DELAY_SECS,
+            retries: None,
+            max_roundtrip_us: None,
+            config: None,
+        }
+    }
+
+    #[test]
+    fn unset_flags_fall_back_to_built_in_defaults_with_no_profile() {
+        let args = resolve_args(bare_cli("main"), &Config::default());
+        assert_eq!(args.data, DEFAULT_DATABASE);
+        assert_eq!(args.comment, DEFAULT_COMMENT);
+        assert!(args.servers.is_empty());
+        assert_eq!(args.retries, DEFAULT_RETRIES);
+        assert_eq!(args.max_roundtrip_us, DEFAULT_MAX_ROUNDTRIP_US);
+    }
+
+    #[test]
+    fn unset_flags_fall_back_to_the_matching_profile() {
+        let mut config = Config {
+            retries: Some(9),
+            ..Default::default()
+        };
+        config.watch.insert(
+            "main".to_string(),
+            WatchProfile {
+                database: Some("profile.sqlite".to_string()),
+                comment: Some("from profile".to_string()),
+                servers: vec!["profile.example.com:123".to_string()],
+            },
+        );
+
+        let args = resolve_args(bare_cli("main"), &config);
+        assert_eq!(args.data, "profile.sqlite");
+        assert_eq!(args.comment, "from profile");
+        assert_eq!(args.servers, vec!["profile.example.com:123".to_string()]);
+        assert_eq!(args.retries, 9);
+    }
+
+    #[test]
+    fn explicit_flags_win_even_when_they_match_the_built_in_default() {
+        let mut config = Config {
+            retries: Some(9),
+            ..Default::default()
+        };
+        config.watch.insert(
+            "main".to_string(),
+            WatchProfile {
+                database: Some("profile.sqlite".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mut cli = bare_cli("main");
+        cli.data = Some(DEFAULT_DATABASE.to_string());
+        cli.retries = Some(DEFAULT_RETRIES);
+
+        let args = resolve_args(cli, &config);
+        // A CLI flag that happens to equal the built-in default must still
+        // win over the profile, since it was explicitly passed.
+        assert_eq!(args.data, DEFAULT_DATABASE);
+        assert_eq!(args.retries, DEFAULT_RETRIES);
+    }
 }
 
 #[derive(Copy, Clone, Default)]
@@ -83,19 +337,26 @@ fn save_to(
     sync: bool,
 ) -> SQLResult<()> {
     let conn = Connection::open(dbname)?;
-    conn.path().map(|path| {
-        println!("Path: {:?}", path.as_os_str());
-    });
+    if let Some(path) = conn.path() {
+        println!("Path: {:?}", path);
+    }
     conn.execute(
         "CREATE TABLE IF NOT EXISTS measurements (
             ts   INTEGER PRIMARY KEY,
             diff INTEGER NOT NULL,
             sync BOOLEAN,
             name TEXT NOT NULL,
-            comment TEXT NULL
+            comment TEXT NULL,
+            predicted_error_rate REAL NULL
         );",
         (), // empty list of parameters.
     )?;
+    // Databases created before the skew predictor predate this column.
+    let _ = conn.execute(
+        "ALTER TABLE measurements ADD COLUMN predicted_error_rate REAL",
+        (),
+    );
+    create_predictions_table(&conn)?;
 
     let mut stmt = conn.prepare("select count(*) from measurements;")?;
     let mut rows = stmt.query(())?;
@@ -103,13 +364,421 @@ fn save_to(
     let n: usize = first.get(0)?;
     let sync = sync || n == 0;
 
+    let checkin = take_pending_prediction(&conn, name, ts, delta)?;
+    let predicted_error_rate = checkin.as_ref().map(|c| c.rate);
+
     match conn.execute(
-        "INSERT INTO measurements(ts, diff, sync, name, comment) VALUES(?1,?2,?3,?4,?5)",
-        (ts, delta, sync, name, comment),
+        "INSERT INTO measurements(ts, diff, sync, name, comment, predicted_error_rate)
+         VALUES(?1,?2,?3,?4,?5,?6)",
+        (ts, delta, sync, name, comment, predicted_error_rate),
     ) {
         Ok(up) => println!("Updated: {up}"),
         Err(e) => println!("Error: {e}"),
     }
+    if let Some(checkin) = checkin {
+        println!(
+            "Prediction error: {:.2}s (actual diff vs. the last `--predict` projection)",
+            checkin.error
+        );
+    }
+
+    Ok(())
+}
+
+/// The `predictions` table holds one pending `--predict` projection per
+/// watch: "at `target_ts` the watch should read true time", stored so the
+/// next measurement can check how close that turned out to be.
+/// `horizon_secs` is the horizon the projection was made over, kept so a
+/// check-in can turn its raw error back into a rate even if the next
+/// `--predict` run uses a different horizon.
+fn create_predictions_table(conn: &Connection) -> SQLResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS predictions (
+            name TEXT PRIMARY KEY,
+            target_ts INTEGER NOT NULL,
+            predicted_diff REAL NOT NULL,
+            horizon_secs REAL NOT NULL
+        );",
+        (),
+    )?;
+    Ok(())
+}
+
+/// Record that, per the crown-set plan just computed, `name` is expected to
+/// read `predicted_diff` seconds off true time at `target_ts`, projected
+/// over `horizon_secs` from crown-set to target. Replaces any previous
+/// pending prediction for this watch.
+fn store_prediction(
+    conn: &Connection,
+    name: &str,
+    target_ts: i64,
+    predicted_diff: f64,
+    horizon_secs: f64,
+) -> SQLResult<()> {
+    create_predictions_table(conn)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO predictions(name, target_ts, predicted_diff, horizon_secs)
+         VALUES(?1,?2,?3,?4)",
+        (name, target_ts, predicted_diff, horizon_secs),
+    )?;
+    Ok(())
+}
+
+/// Average of the last `PREDICTION_BIAS_WINDOW` `predicted_error_rate`
+/// readings for `name`, or `0.0` if none have checked in yet. Feeding this
+/// back into the next projection is the closed-loop part: keeping it as a
+/// rate rather than a raw seconds figure lets it rescale to whatever
+/// horizon the next `--predict` run asks for.
+fn recent_prediction_bias(conn: &Connection, name: &str) -> SQLResult<f64> {
+    let mut stmt = conn.prepare(
+        "SELECT predicted_error_rate FROM measurements
+         WHERE name = ?1 AND predicted_error_rate IS NOT NULL
+         ORDER BY ts DESC LIMIT ?2",
+    )?;
+    let rates = stmt
+        .query_map((name, PREDICTION_BIAS_WINDOW as i64), |row| {
+            row.get::<_, f64>(0)
+        })?
+        .collect::<SQLResult<Vec<f64>>>()?;
+
+    if rates.is_empty() {
+        return Ok(0.0);
+    }
+    Ok(rates.iter().sum::<f64>() / rates.len() as f64)
+}
+
+/// A pending prediction checking in against an actual measurement.
+struct PredictionCheckin {
+    /// `delta - predicted_diff`: the error between the projection and what
+    /// the watch actually read.
+    error: f64,
+    /// `error` divided by the horizon that projection was made over.
+    rate: f64,
+}
+
+/// If `name` has a pending prediction and `ts` is within
+/// `PREDICTION_TOLERANCE_SECS` of its target, consume it and return the
+/// checked-in error and rate; otherwise `None`. A prediction is only ever
+/// cleared once it's checked in or expired, so an ordinary measurement
+/// taken while it's still pending leaves it in place.
+fn take_pending_prediction(
+    conn: &Connection,
+    name: &str,
+    ts: u32,
+    delta: i32,
+) -> SQLResult<Option<PredictionCheckin>> {
+    let pending: Option<(i64, f64, f64)> = conn
+        .query_row(
+            "SELECT target_ts, predicted_diff, horizon_secs FROM predictions WHERE name = ?1",
+            (name,),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+
+    let Some((target_ts, predicted_diff, horizon_secs)) = pending else {
+        return Ok(None);
+    };
+
+    let since_target = ts as i64 - target_ts;
+    if since_target < -PREDICTION_TOLERANCE_SECS {
+        // Too early to be a check-in on this prediction; leave it pending.
+        return Ok(None);
+    }
+    conn.execute("DELETE FROM predictions WHERE name = ?1", (name,))?;
+
+    if since_target.abs() > PREDICTION_TOLERANCE_SECS {
+        // Expired without a check-in close enough to the target.
+        return Ok(None);
+    }
+    let error = delta as f64 - predicted_diff;
+    let rate = if horizon_secs != 0.0 {
+        error / horizon_secs
+    } else {
+        0.0
+    };
+    Ok(Some(PredictionCheckin { error, rate }))
+}
+
+#[cfg(test)]
+mod prediction_tests {
+    use super::*;
+
+    const NAME: &str = "main";
+    const TARGET_TS: i64 = 1_000_000;
+    const HORIZON_SECS: f64 = 86_400.0; // one day
+    const PREDICTED_DIFF: f64 = 0.0;
+
+    fn conn_with_pending_prediction() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_predictions_table(&conn).unwrap();
+        store_prediction(&conn, NAME, TARGET_TS, PREDICTED_DIFF, HORIZON_SECS).unwrap();
+        conn
+    }
+
+    #[test]
+    fn checks_in_within_tolerance_and_clears_the_prediction() {
+        let conn = conn_with_pending_prediction();
+
+        let checkin = take_pending_prediction(&conn, NAME, (TARGET_TS + 60) as u32, 43).unwrap();
+        let checkin = checkin.expect("should check in: within tolerance of the target");
+        assert_eq!(checkin.error, 43.0);
+        assert!((checkin.rate - 43.0 / HORIZON_SECS).abs() < 1e-9);
+
+        // The prediction is consumed either way.
+        assert!(take_pending_prediction(&conn, NAME, (TARGET_TS + 61) as u32, 0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn leaves_an_early_measurement_pending() {
+        let conn = conn_with_pending_prediction();
+
+        let too_early = TARGET_TS - PREDICTION_TOLERANCE_SECS - 1;
+        assert!(take_pending_prediction(&conn, NAME, too_early as u32, 5)
+            .unwrap()
+            .is_none());
+
+        // Still pending: a measurement within tolerance afterwards checks in.
+        let checkin = take_pending_prediction(&conn, NAME, TARGET_TS as u32, 7)
+            .unwrap()
+            .expect("prediction should still be pending");
+        assert_eq!(checkin.error, 7.0);
+    }
+
+    #[test]
+    fn expires_a_measurement_taken_too_late() {
+        let conn = conn_with_pending_prediction();
+
+        let too_late = TARGET_TS + PREDICTION_TOLERANCE_SECS + 1;
+        assert!(take_pending_prediction(&conn, NAME, too_late as u32, 5)
+            .unwrap()
+            .is_none());
+
+        // Expired predictions are cleared, not left pending forever.
+        assert!(take_pending_prediction(&conn, NAME, TARGET_TS as u32, 0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn no_pending_prediction_is_a_no_op() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_predictions_table(&conn).unwrap();
+        assert!(take_pending_prediction(&conn, NAME, TARGET_TS as u32, 5)
+            .unwrap()
+            .is_none());
+    }
+}
+
+/// One straight-line fit of `diff = a + b*ts` over a span of measurements,
+/// expressed as a drift rate.
+struct DriftFit {
+    start_ts: i64,
+    end_ts: i64,
+    n: usize,
+    /// Fitted diff at `start_ts`, i.e. `a` in `diff = a + b*(ts - start_ts)`.
+    intercept: f64,
+    rate_per_day: f64,
+    stderr_per_day: f64,
+}
+
+/// Least-squares fit of `diff = a + b*ts` over `points`, returning the slope
+/// `b` converted to seconds/day along with its standard error. `None` if
+/// there are fewer than two points or they all share the same timestamp.
+fn fit_drift(points: &[(i64, f64)]) -> Option<DriftFit> {
+    let n = points.len();
+    if n < 2 {
+        return None;
+    }
+
+    let t0 = points[0].0;
+    let xs: Vec<f64> = points.iter().map(|&(ts, _)| (ts - t0) as f64).collect();
+    let ys: Vec<f64> = points.iter().map(|&(_, diff)| diff).collect();
+
+    let n_f = n as f64;
+    let mean_x = xs.iter().sum::<f64>() / n_f;
+    let mean_y = ys.iter().sum::<f64>() / n_f;
+
+    let mut sxx = 0.0;
+    let mut sxy = 0.0;
+    for i in 0..n {
+        let dx = xs[i] - mean_x;
+        sxx += dx * dx;
+        sxy += dx * (ys[i] - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let b = sxy / sxx;
+    let a = mean_y - b * mean_x;
+
+    let sse: f64 = (0..n)
+        .map(|i| {
+            let resid = ys[i] - (a + b * xs[i]);
+            resid * resid
+        })
+        .sum();
+    let dof = (n_f - 2.0).max(1.0);
+    let stderr_b = (sse / dof / sxx).sqrt();
+
+    const SECONDS_PER_DAY: f64 = 86_400.0;
+    Some(DriftFit {
+        start_ts: points[0].0,
+        end_ts: points[n - 1].0,
+        n,
+        intercept: a,
+        rate_per_day: b * SECONDS_PER_DAY,
+        stderr_per_day: stderr_b * SECONDS_PER_DAY,
+    })
+}
+
+/// Read all `(ts, diff, sync)` rows for `name`, ordered by time.
+fn load_measurements(conn: &Connection, name: &str) -> SQLResult<Vec<(i64, f64, bool)>> {
+    let mut stmt =
+        conn.prepare("SELECT ts, diff, sync FROM measurements WHERE name = ?1 ORDER BY ts ASC")?;
+    let rows = stmt.query_map((name,), |row| {
+        let ts: i64 = row.get(0)?;
+        let diff: i64 = row.get(1)?;
+        let sync: bool = row.get(2)?;
+        Ok((ts, diff as f64, sync))
+    })?;
+    rows.collect()
+}
+
+/// Split measurements into drift-accumulation intervals, starting a new one
+/// at every `sync = true` row (a crown reset restarts the drift baseline).
+fn split_sync_intervals(rows: &[(i64, f64, bool)]) -> Vec<Vec<(i64, f64)>> {
+    let mut intervals: Vec<Vec<(i64, f64)>> = Vec::new();
+    for &(ts, diff, sync) in rows {
+        if sync || intervals.is_empty() {
+            intervals.push(Vec::new());
+        }
+        intervals.last_mut().unwrap().push((ts, diff));
+    }
+    intervals
+}
+
+/// Print the drift-rate (seconds/day) of `name`, per sync interval and over
+/// the whole history.
+fn run_report(args: &Args) -> SQLResult<()> {
+    let conn = Connection::open(args.data.as_str())?;
+    let rows = load_measurements(&conn, &args.name)?;
+    if rows.is_empty() {
+        println!("No measurements found for watch '{}'.", args.name);
+        return Ok(());
+    }
+
+    println!("Drift report for '{}' ({} measurements)", args.name, rows.len());
+    println!(
+        "{:>12} {:>12} {:>6} {:>14} {:>16}",
+        "from", "to", "n", "s/day", "stderr (s/day)"
+    );
+    for interval in split_sync_intervals(&rows) {
+        match fit_drift(&interval) {
+            Some(fit) => println!(
+                "{:>12} {:>12} {:>6} {:>14.3} {:>16.3}",
+                fit.start_ts, fit.end_ts, fit.n, fit.rate_per_day, fit.stderr_per_day
+            ),
+            None => println!("(not enough points in this sync interval to fit a rate)"),
+        }
+    }
+
+    let points: Vec<(i64, f64)> = rows.iter().map(|&(ts, diff, _)| (ts, diff)).collect();
+    match fit_drift(&points) {
+        Some(fit) => println!(
+            "\nOverall: {} measurements over {} s, drift {:.3} s/day (stderr {:.3} s/day)",
+            fit.n,
+            fit.end_ts - fit.start_ts,
+            fit.rate_per_day,
+            fit.stderr_per_day
+        ),
+        None => println!("\nNot enough measurements for an overall fit."),
+    }
+
+    Ok(())
+}
+
+/// Model the watch as `watch_time(t) = true_time(t) + offset0 + rate*(t -
+/// t0)`, bias-corrected by `recent_prediction_bias`, report how far off
+/// that leaves it right now, then invert the model to find the crown-set
+/// instant: the dial value to set *now* (plus `manipulation_delay_secs` to
+/// physically do it) so the watch reads correctly at the target time. The
+/// projection is stashed in the `predictions` table so the next
+/// measurement can check in on it and feed the next round's bias.
+async fn run_predict(args: &Args) -> Result<(), Error> {
+    let conn = match Connection::open(args.data.as_str()) {
+        Ok(conn) => conn,
+        Err(err) => {
+            println!("An error occured: {err}");
+            return Ok(());
+        }
+    };
+    let rows = match load_measurements(&conn, &args.name) {
+        Ok(rows) => rows,
+        Err(err) => {
+            println!("An error occured: {err}");
+            return Ok(());
+        }
+    };
+    let Some(interval) = split_sync_intervals(&rows).into_iter().last() else {
+        println!("No measurements found for watch '{}'.", args.name);
+        return Ok(());
+    };
+    let Some(fit) = fit_drift(&interval) else {
+        println!(
+            "Not enough measurements since the last sync to fit a drift rate for '{}'.",
+            args.name
+        );
+        return Ok(());
+    };
+
+    let servers = if args.servers.is_empty() {
+        DEFAULT_SERVERS.iter().map(|s| s.to_string()).collect()
+    } else {
+        args.servers.clone()
+    };
+    let sample = poll_servers(&servers, args.retries, args.max_roundtrip_us).await?;
+    let now = chrono::Utc::now() + chrono::Duration::microseconds(sample.result.offset());
+
+    let bias_rate = match recent_prediction_bias(&conn, &args.name) {
+        Ok(bias_rate) => bias_rate,
+        Err(err) => {
+            println!("Could not load past prediction accuracy, assuming no bias: {err}");
+            0.0
+        }
+    };
+    let rate_per_sec = fit.rate_per_day / 86_400.0;
+    let elapsed_since_fit = (now.timestamp() - fit.start_ts) as f64;
+    let projected_error_secs =
+        fit.intercept + rate_per_sec * elapsed_since_fit + bias_rate * elapsed_since_fit;
+
+    let set_at = now + chrono::Duration::seconds(args.manipulation_delay_secs);
+    let target_at = now + chrono::Duration::seconds(args.predict_horizon_secs);
+    let horizon_from_set_secs = (target_at - set_at).num_milliseconds() as f64 / 1000.0;
+    let projected_drift_secs =
+        rate_per_sec * horizon_from_set_secs + bias_rate * horizon_from_set_secs;
+    let set_to =
+        set_at - chrono::Duration::milliseconds((projected_drift_secs * 1000.0) as i64);
+
+    println!(
+        "Watch '{}' is drifting at {:.3} s/day (stderr {:.3}); projected to be off by {:.2}s right now (bias-corrected at {:.2e} s/s from past predictions).",
+        args.name, fit.rate_per_day, fit.stderr_per_day, projected_error_secs, bias_rate
+    );
+    println!(
+        "At {set_at}, set the crown to read {set_to} (not {set_at}) so it reads correctly at {target_at}."
+    );
+
+    if let Err(err) = store_prediction(
+        &conn,
+        &args.name,
+        target_at.timestamp(),
+        0.0,
+        horizon_from_set_secs,
+    ) {
+        println!("Could not save this projection for self-correction: {err}");
+    }
 
     Ok(())
 }
@@ -124,21 +793,33 @@ async fn gui(args: &Args) -> Result<(), Error> {
             .button("12", |s| s.quit()),
     );
 
-    let ref_time = get_ntp_time().await?;
+    let servers = if args.servers.is_empty() {
+        DEFAULT_SERVERS.iter().map(|s| s.to_string()).collect()
+    } else {
+        args.servers.clone()
+    };
+    let sample = poll_servers(&servers, args.retries, args.max_roundtrip_us).await?;
+    println!(
+        "NTP server: {} (offset: {} us, round-trip delay: {} us)",
+        sample.server,
+        sample.result.offset(),
+        sample.result.roundtrip()
+    );
     let start = chrono::Utc::now();
     siv.run();
     let click = chrono::Utc::now();
     let duration = click.signed_duration_since(start);
 
-    let sec = ref_time.sec();
-    let ms = (ref_time.sec_fraction() as u64) * 1000 / u32::MAX as u64;
-    let click_dt = Utc
-        .timestamp_opt(sec as i64, (ms * 1_000_000u64) as u32)
-        .single()
-        .expect("Unuable to convert timestamp")
+    // Correct the click by the measured NTP offset so `diff` reflects the
+    // watch, not the one-way network delay.
+    let click_dt = start
+        .checked_add_signed(chrono::Duration::microseconds(sample.result.offset()))
+        .expect("Failed to apply NTP offset")
         .checked_add_signed(duration)
         .expect("Failed to add duration");
 
+    let sec = click_dt.timestamp() as u32;
+
     let mut minute_group: RadioGroup<i32> = RadioGroup::new();
 
     let tm1 = (click_dt.minute() + 59) % 60;
@@ -169,43 +850,246 @@ async fn gui(args: &Args) -> Result<(), Error> {
     let delta = minute_group
         .selection()
         .checked_sub(click_dt.second() as i32);
-    match delta {
-        Some(delta) => {
-            let res = save_to(
-                args.data.as_str(),
-                sec,
-                delta,
-                &args.name,
-                &args.comment,
-                args.sync,
-            );
-            let _ = res.map_err(|err| {
-                println!("An error occured: {}", err.to_string());
-            });
-            siv.pop_layer();
-            // And we simply print the result.
-            let text = format!("Difference is {:?}s", delta);
-            siv.add_layer(Dialog::text(text).button("Ok", |s| s.quit()));
-            siv.run();
+    if let Some(delta) = delta {
+        let res = save_to(
+            args.data.as_str(),
+            sec,
+            delta,
+            &args.name,
+            &args.comment,
+            args.sync,
+        );
+        if let Err(err) = res {
+            println!("An error occured: {err}");
         }
-        None => (),
+        siv.pop_layer();
+        // And we simply print the result.
+        let text = format!("Difference is {:?}s", delta);
+        siv.add_layer(Dialog::text(text).button("Ok", |s| s.quit()));
+        siv.run();
     }
 
     Ok(())
 }
 
-async fn get_ntp_time() -> Result<NtpResult, Error> {
+async fn get_ntp_time(host: &str) -> Result<NtpResult, Error> {
     let socket = UdpSocket::bind("0.0.0.0:0").expect("Unable to crate UDP socket");
     socket
         .set_read_timeout(Some(Duration::from_secs(2)))
         .expect("Unable to set UDP socket read timeout");
     let sock_wrapper = UdpSocketWrapper(socket);
     let ntp_context = NtpContext::new(StdTimestampGen::default());
-    sntpc::get_time("time.cloudflare.com:123", sock_wrapper, ntp_context)
+    sntpc::get_time(host, &sock_wrapper, ntp_context)
+}
+
+/// Fold `candidate` into `best`, keeping whichever has the lower round-trip
+/// delay and dropping anything over `max_roundtrip_us` as too noisy to trust.
+fn fold_best_sample(
+    best: Option<NtpResult>,
+    candidate: NtpResult,
+    max_roundtrip_us: u64,
+) -> Option<NtpResult> {
+    if candidate.roundtrip() > max_roundtrip_us {
+        return best;
+    }
+    match best {
+        Some(b) if b.roundtrip() <= candidate.roundtrip() => Some(b),
+        _ => Some(candidate),
+    }
+}
+
+/// Take up to `retries` NTP readings from `host` and keep the one with the
+/// lowest round-trip delay, discarding any over `max_roundtrip_us`.
+async fn best_ntp_sample(
+    host: &str,
+    retries: usize,
+    max_roundtrip_us: u64,
+) -> Result<NtpResult, Error> {
+    let mut best: Option<NtpResult> = None;
+    for _ in 0..retries {
+        let Ok(result) = get_ntp_time(host).await else {
+            continue;
+        };
+        best = fold_best_sample(best, result, max_roundtrip_us);
+    }
+    best.ok_or(Error::Network)
+}
+
+/// An NTP reading together with the server that produced it.
+#[derive(Debug)]
+struct NtpSample {
+    server: String,
+    result: NtpResult,
+}
+
+/// Fold `candidate` into `best`, keeping whichever has the lower round-trip
+/// delay. A single flaky or unreachable server can't silently skew the
+/// measurement this way: we only need one of them to respond well.
+fn fold_best_server_sample(best: Option<NtpSample>, candidate: NtpSample) -> Option<NtpSample> {
+    match &best {
+        Some(b) if b.result.roundtrip() <= candidate.result.roundtrip() => best,
+        _ => Some(candidate),
+    }
+}
+
+/// Poll every host in `servers`, retrying up to `retries` times per host,
+/// and return the reading with the lowest round-trip delay across all
+/// reachable servers.
+async fn poll_servers(
+    servers: &[String],
+    retries: usize,
+    max_roundtrip_us: u64,
+) -> Result<NtpSample, Error> {
+    let mut best: Option<NtpSample> = None;
+    for server in servers {
+        let Ok(result) = best_ntp_sample(server, retries, max_roundtrip_us).await else {
+            continue;
+        };
+        best = fold_best_server_sample(
+            best,
+            NtpSample {
+                server: server.clone(),
+                result,
+            },
+        );
+    }
+    best.ok_or(Error::Network)
 }
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
+    let config = load_config(cli.config.as_deref());
+    let args = resolve_args(cli, &config);
+    if args.report {
+        if let Err(err) = run_report(&args) {
+            println!("An error occured: {err}");
+        }
+        return;
+    }
+    if args.predict {
+        if let Err(err) = run_predict(&args).await {
+            println!("An error occured: {err:?}");
+        }
+        return;
+    }
     let _a = gui(&args).await;
 }
+
+#[cfg(test)]
+mod ntp_sample_tests {
+    use super::*;
+
+    fn sample(roundtrip: u64) -> NtpResult {
+        NtpResult::new(0, 0, roundtrip, 0, 1, 0)
+    }
+
+    #[test]
+    fn keeps_the_lower_roundtrip_candidate() {
+        let best = fold_best_sample(Some(sample(500)), sample(200), 150_000);
+        assert_eq!(best.unwrap().roundtrip(), 200);
+    }
+
+    #[test]
+    fn ignores_a_candidate_over_the_threshold() {
+        let best = fold_best_sample(Some(sample(500)), sample(200_000), 150_000);
+        assert_eq!(best.unwrap().roundtrip(), 500);
+    }
+
+    #[test]
+    fn a_noisy_first_candidate_is_dropped_entirely() {
+        assert!(fold_best_sample(None, sample(200_000), 150_000).is_none());
+    }
+}
+
+#[cfg(test)]
+mod server_sample_tests {
+    use super::*;
+
+    fn server_sample(server: &str, roundtrip: u64) -> NtpSample {
+        NtpSample {
+            server: server.to_string(),
+            result: NtpResult::new(0, 0, roundtrip, 0, 1, 0),
+        }
+    }
+
+    #[test]
+    fn picks_the_server_with_the_lower_roundtrip() {
+        let best = fold_best_server_sample(
+            Some(server_sample("a", 500)),
+            server_sample("b", 200),
+        );
+        assert_eq!(best.unwrap().server, "b");
+    }
+
+    #[test]
+    fn keeps_the_first_server_on_a_tie() {
+        let best = fold_best_server_sample(
+            Some(server_sample("a", 200)),
+            server_sample("b", 200),
+        );
+        assert_eq!(best.unwrap().server, "a");
+    }
+}
+
+#[cfg(test)]
+mod drift_tests {
+    use super::*;
+
+    #[test]
+    fn fit_drift_recovers_known_slope() {
+        const SECONDS_PER_DAY: f64 = 86_400.0;
+        let points: Vec<(i64, f64)> = (0..5)
+            .map(|i| {
+                let ts = i * 86_400;
+                (ts, 2.0 + 0.5 * (ts as f64) / SECONDS_PER_DAY)
+            })
+            .collect();
+
+        let fit = fit_drift(&points).expect("fit should succeed with enough points");
+        assert_eq!(fit.n, 5);
+        assert_eq!(fit.start_ts, 0);
+        assert_eq!(fit.end_ts, 4 * 86_400);
+        assert!((fit.intercept - 2.0).abs() < 1e-9);
+        assert!((fit.rate_per_day - 0.5).abs() < 1e-9);
+        assert!(fit.stderr_per_day.abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_drift_needs_at_least_two_distinct_timestamps() {
+        assert!(fit_drift(&[]).is_none());
+        assert!(fit_drift(&[(0, 1.0)]).is_none());
+        assert!(fit_drift(&[(0, 1.0), (0, 2.0)]).is_none());
+    }
+
+    #[test]
+    fn split_sync_intervals_starts_a_new_interval_at_each_sync() {
+        let rows = vec![
+            (0, 0.0, false),
+            (1, 1.0, false),
+            (2, 0.0, true),
+            (3, 1.0, false),
+            (4, 2.0, false),
+        ];
+        let intervals = split_sync_intervals(&rows);
+        assert_eq!(
+            intervals,
+            vec![
+                vec![(0, 0.0), (1, 1.0)],
+                vec![(2, 0.0), (3, 1.0), (4, 2.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn split_sync_intervals_treats_a_leading_sync_as_starting_the_first_interval() {
+        let rows = vec![(0, 0.0, true), (1, 1.0, false)];
+        let intervals = split_sync_intervals(&rows);
+        assert_eq!(intervals, vec![vec![(0, 0.0), (1, 1.0)]]);
+    }
+
+    #[test]
+    fn split_sync_intervals_of_no_rows_is_empty() {
+        assert!(split_sync_intervals(&[]).is_empty());
+    }
+}